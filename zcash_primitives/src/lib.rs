@@ -17,6 +17,8 @@ pub mod sapling;
 mod serialize;
 pub mod transaction;
 
+pub use serialize::{read_compact_size_peek, CountingReader, CountingWriter, LimitedReader};
+
 lazy_static! {
     pub static ref JUBJUB: JubjubBls12 = { JubjubBls12::new() };
 }
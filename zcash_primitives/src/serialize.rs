@@ -1,4 +1,5 @@
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::cmp;
 use std::io::{self, Read, Write};
 
 const MAX_SIZE: usize = 0x02000000;
@@ -62,6 +63,29 @@ impl CompactSize {
     }
 }
 
+/// Reads and consumes a `CompactSize` from `reader`, returning its value along with
+/// the raw bytes that encoded it.
+///
+/// This lets a caller inspect the leading count of a structure on a reader that
+/// cannot seek. The structure can then be parsed as usual from
+/// `(&bytes[..]).chain(reader)`.
+pub fn read_compact_size_peek<R: Read>(reader: &mut R) -> io::Result<(u64, Vec<u8>)> {
+    let flag = reader.read_u8()?;
+    let len = match flag {
+        253 => 2,
+        254 => 4,
+        255 => 8,
+        _ => 0,
+    };
+
+    let mut bytes = vec![flag];
+    bytes.resize(1 + len, 0);
+    reader.read_exact(&mut bytes[1..])?;
+
+    let size = CompactSize::read(&bytes[..])?;
+    Ok((size as u64, bytes))
+}
+
 pub struct Vector;
 
 impl Vector {
@@ -82,6 +106,110 @@ impl Vector {
     }
 }
 
+/// A reader that returns an error once more than a fixed number of bytes have been
+/// read from the underlying reader.
+///
+/// Parsers such as `Transaction::read` trust the `CompactSize` lengths they encounter,
+/// so untrusted streams should be wrapped in a `LimitedReader` before being parsed.
+/// This bounds the work done on a malicious length to the configured budget.
+pub struct LimitedReader<R> {
+    inner: R,
+    remaining: usize,
+}
+
+impl<R: Read> LimitedReader<R> {
+    /// Wraps `inner`, allowing at most `limit` bytes to be read from it. Reading past
+    /// `limit` returns an error, unless `inner` is already at EOF.
+    pub fn new(inner: R, limit: usize) -> Self {
+        LimitedReader {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            // The budget is spent; this is only an error if the inner stream has more.
+            let mut probe = [0; 1];
+            return match self.inner.read(&mut probe)? {
+                0 => Ok(0),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "read limit exceeded",
+                )),
+            };
+        }
+
+        let max = cmp::min(buf.len(), self.remaining);
+        let n = self.inner.read(&mut buf[..max])?;
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// A reader that tracks the number of bytes read from the underlying reader.
+pub struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        CountingReader { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> usize {
+        self.count
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+/// A writer that tracks the number of bytes written to the underlying writer.
+pub struct CountingWriter<W> {
+    inner: W,
+    count: usize,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        self.count
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,6 +252,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compact_size_peek() {
+        for &value in &[0, 252, 253, 65536, 33554432] {
+            let mut data = vec![];
+            CompactSize::write(&mut data, value).unwrap();
+            data.extend_from_slice(&[1, 2, 3]);
+
+            let mut reader = &data[..];
+            let (size, bytes) = read_compact_size_peek(&mut reader).unwrap();
+            assert_eq!(size, value as u64);
+            assert_eq!(reader, &[1, 2, 3][..]);
+
+            let mut chained = (&bytes[..]).chain(reader);
+            assert_eq!(CompactSize::read(&mut chained).unwrap(), value);
+            let mut rest = vec![];
+            chained.read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, [1, 2, 3]);
+        }
+
+        // Non-canonical encodings are still rejected.
+        assert!(read_compact_size_peek(&mut &[253u8, 252, 0][..]).is_err());
+    }
+
     #[test]
     fn vector() {
         macro_rules! eval {
@@ -153,4 +304,76 @@ mod tests {
             eval!(vec![7; 260], expected);
         }
     }
+
+    #[test]
+    fn limited_reader() {
+        let data = [1, 2, 3, 4, 5];
+
+        let mut buf = [0; 5];
+        let mut reader = LimitedReader::new(&data[..], 5);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        let mut reader = LimitedReader::new(&data[..], 4);
+        let err = reader.read_exact(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn limited_reader_exact_length() {
+        let data = [1, 2, 3];
+
+        let mut buf = vec![];
+        let mut reader = LimitedReader::new(&data[..], 3);
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        let empty: &[u8] = &[];
+        let mut buf = vec![];
+        let mut reader = LimitedReader::new(empty, 0);
+        reader.read_to_end(&mut buf).unwrap();
+        assert!(buf.is_empty());
+
+        let mut buf = vec![];
+        let mut reader = LimitedReader::new(&data[..], 2);
+        let err = reader.read_to_end(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn limited_reader_huge_vector() {
+        // CompactSize claiming MAX_SIZE elements, followed by an endless stream.
+        let prefix = [254, 0, 0, 0, 2];
+        let stream = (&prefix[..]).chain(io::repeat(7));
+
+        let reader = LimitedReader::new(stream, 1000);
+        match Vector::read(reader, |r| r.read_u8()) {
+            Ok(_) => panic!("Expected read limit to be exceeded"),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+        }
+    }
+
+    #[test]
+    fn counting_reader() {
+        let data = [1, 2, 3, 4, 5];
+        let mut reader = CountingReader::new(&data[..]);
+        assert_eq!(reader.bytes_read(), 0);
+
+        reader.read_u8().unwrap();
+        assert_eq!(reader.bytes_read(), 1);
+        reader.read_u32::<LittleEndian>().unwrap();
+        assert_eq!(reader.bytes_read(), 5);
+
+        assert!(reader.read_u8().is_err());
+        assert_eq!(reader.bytes_read(), 5);
+    }
+
+    #[test]
+    fn counting_writer() {
+        let mut writer = CountingWriter::new(io::sink());
+        assert_eq!(writer.bytes_written(), 0);
+
+        Vector::write(&mut writer, &[7u8; 260], |w, e| w.write_u8(*e)).unwrap();
+        assert_eq!(writer.bytes_written(), 263);
+    }
 }
@@ -5,6 +5,7 @@ use sapling_crypto::{
     jubjub::{edwards, Unknown},
     redjubjub::{PublicKey, Signature},
 };
+use std::cmp;
 use std::io::{self, Read, Write};
 
 use serialize::Vector;
@@ -142,6 +143,17 @@ impl TxOut {
     }
 }
 
+/// Returns the number of ZIP 317 logical actions contributed by the transparent inputs
+/// and outputs of a transaction.
+///
+/// ZIP 317 counts transparent logical actions by dividing the total input and output
+/// sizes by the standard P2PKH input and output sizes (150 and 34 bytes). This assumes
+/// every input and output is standard P2PKH, in which case the count reduces to the
+/// larger of the number of inputs and the number of outputs.
+pub fn transparent_logical_actions(num_inputs: usize, num_outputs: usize) -> usize {
+    cmp::max(num_inputs, num_outputs)
+}
+
 pub struct SpendDescription {
     pub cv: edwards::Point<Bls12, Unknown>,
     pub anchor: Fr,
@@ -449,7 +461,7 @@ impl JSDescription {
 
 #[cfg(test)]
 mod tests {
-    use super::{Amount, MAX_MONEY};
+    use super::{transparent_logical_actions, Amount, MAX_MONEY};
 
     #[test]
     fn amount_in_range() {
@@ -492,4 +504,14 @@ mod tests {
         assert!(Amount::read_i64(&neg_max_money_m1[..], false).is_err());
         assert!(Amount::read_i64(&neg_max_money_m1[..], true).is_err());
     }
+
+    #[test]
+    fn zip_0317_transparent_logical_actions() {
+        assert_eq!(transparent_logical_actions(0, 0), 0);
+        assert_eq!(transparent_logical_actions(1, 0), 1);
+        assert_eq!(transparent_logical_actions(0, 1), 1);
+        assert_eq!(transparent_logical_actions(1, 1), 1);
+        assert_eq!(transparent_logical_actions(1, 2), 2);
+        assert_eq!(transparent_logical_actions(5, 3), 5);
+    }
 }
@@ -6,7 +6,7 @@ use std::fmt;
 use std::io::{self, Read, Write};
 use std::ops::Deref;
 
-use serialize::Vector;
+use serialize::{CountingReader, Vector};
 
 pub mod components;
 mod sighash;
@@ -34,6 +34,37 @@ impl fmt::Display for TxId {
     }
 }
 
+/// The location at which a transaction failed to parse.
+#[derive(Debug)]
+pub struct ParseDiagnostic {
+    /// The number of bytes that had been consumed when parsing failed.
+    ///
+    /// This includes any bytes of the failing field that were already consumed, so
+    /// for example data truncated partway through `lock_time` reports the end of the
+    /// data rather than the start of the field.
+    pub offset: usize,
+    /// The transaction field that was being read when parsing failed.
+    pub component: &'static str,
+    /// The underlying error returned while reading the field.
+    pub error: io::Error,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse {} at byte {}: {}",
+            self.component, self.offset, self.error
+        )
+    }
+}
+
+impl std::error::Error for ParseDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 /// A Zcash transaction.
 #[derive(Debug)]
 pub struct Transaction {
@@ -152,7 +183,25 @@ impl Transaction {
         self.txid
     }
 
-    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        Transaction::read_labelled(reader, &mut "header")
+    }
+
+    /// Parses a transaction from `data`, reporting where parsing failed if the
+    /// encoding is invalid.
+    pub fn parse_with_diagnostics(data: &[u8]) -> Result<Self, ParseDiagnostic> {
+        let mut reader = CountingReader::new(data);
+        let mut component = "header";
+        Transaction::read_labelled(&mut reader, &mut component).map_err(|error| ParseDiagnostic {
+            offset: reader.bytes_read(),
+            component,
+            error,
+        })
+    }
+
+    /// Reads a transaction, recording in `component` the field currently being read.
+    fn read_labelled<R: Read>(mut reader: R, component: &mut &'static str) -> io::Result<Self> {
+        *component = "header";
         let header = reader.read_u32::<LittleEndian>()?;
         let overwintered = (header >> 31) == 1;
         let version = header & 0x7FFFFFFF;
@@ -175,17 +224,24 @@ impl Transaction {
             ));
         }
 
+        *component = "vin";
         let vin = Vector::read(&mut reader, TxIn::read)?;
+        *component = "vout";
         let vout = Vector::read(&mut reader, TxOut::read)?;
+        *component = "lock_time";
         let lock_time = reader.read_u32::<LittleEndian>()?;
+        *component = "expiry_height";
         let expiry_height = match is_overwinter_v3 || is_sapling_v4 {
             true => reader.read_u32::<LittleEndian>()?,
             false => 0,
         };
 
         let (value_balance, shielded_spends, shielded_outputs) = if is_sapling_v4 {
+            *component = "value_balance";
             let vb = Amount::read_i64(&mut reader, true)?;
+            *component = "shielded_spends";
             let ss = Vector::read(&mut reader, SpendDescription::read)?;
+            *component = "shielded_outputs";
             let so = Vector::read(&mut reader, OutputDescription::read)?;
             (vb, ss, so)
         } else {
@@ -193,13 +249,16 @@ impl Transaction {
         };
 
         let (joinsplits, joinsplit_pubkey, joinsplit_sig) = if version >= 2 {
+            *component = "joinsplits";
             let jss = Vector::read(&mut reader, |r| {
                 JSDescription::read(r, overwintered && version >= SAPLING_TX_VERSION)
             })?;
             let (pubkey, sig) = if !jss.is_empty() {
                 let mut joinsplit_pubkey = [0; 32];
                 let mut joinsplit_sig = [0; 64];
+                *component = "joinsplit_pubkey";
                 reader.read_exact(&mut joinsplit_pubkey)?;
+                *component = "joinsplit_sig";
                 reader.read_exact(&mut joinsplit_sig)?;
                 (Some(joinsplit_pubkey), Some(joinsplit_sig))
             } else {
@@ -210,6 +269,7 @@ impl Transaction {
             (vec![], None, None)
         };
 
+        *component = "binding_sig";
         let binding_sig =
             match is_sapling_v4 && !(shielded_spends.is_empty() && shielded_outputs.is_empty()) {
                 true => Some(Signature::read(&mut reader)?),
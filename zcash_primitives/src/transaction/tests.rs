@@ -1,6 +1,7 @@
 use pairing::bls12_381::Bls12;
 use rand::{thread_rng, Rng};
 use sapling_crypto::{jubjub::FixedGenerators, redjubjub::PrivateKey};
+use std::error;
 
 use super::{
     components::{Amount, Script},
@@ -208,6 +209,36 @@ fn tx_write_rejects_unexpected_binding_sig() {
     }
 }
 
+#[test]
+fn tx_parse_diagnostics() {
+    let mut data = vec![];
+    TransactionData::new()
+        .freeze()
+        .unwrap()
+        .write(&mut data)
+        .unwrap();
+    assert!(Transaction::parse_with_diagnostics(&data).is_ok());
+
+    // Truncated before lock_time
+    {
+        let diag = Transaction::parse_with_diagnostics(&data[..10]).unwrap_err();
+        assert_eq!(diag.component, "lock_time");
+        assert_eq!(diag.offset, 10);
+    }
+
+    // value_balance out of range
+    {
+        let mut data = data.clone();
+        data[25] = 0x7f;
+        let diag = Transaction::parse_with_diagnostics(&data).unwrap_err();
+        assert_eq!(diag.component, "value_balance");
+        assert_eq!(diag.offset, 26);
+
+        let source = error::Error::source(&diag).unwrap();
+        assert_eq!(source.to_string(), diag.error.to_string());
+    }
+}
+
 #[test]
 fn zip_0143() {
     struct TestVector {